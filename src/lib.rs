@@ -1,5 +1,6 @@
 #![allow(static_mut_refs)]
 use object::{Object as _, ObjectSection};
+use std::collections::{HashMap, VecDeque};
 use std::io::{Read, Seek, SeekFrom};
 use std::sync::RwLock;
 use std::{
@@ -11,8 +12,22 @@ unsafe extern "C" {
     /// Read a chunk of bytes from the wasm module with the given id into the specified dest of this module's memory.
     fn read_chunk(dest: *mut u8, src: usize, len: usize) -> usize;
 
-    /// Called during address_to_frame so that JS can copy the frame data out of the wasm memory. If
-    /// `symbol` is null, the original line should be used.
+    /// Read a chunk of bytes from a standalone split DWARF object (`.dwo`) identified
+    /// by the UTF-8 string at `id_ptr`/`id_len`, into `dest`, starting at `offset`. Mirrors
+    /// `read_chunk`'s semantics: returns the number of bytes actually written, and 0 once the file is
+    /// exhausted.
+    /// Bundled `.dwp` packages (indexed via `.debug_cu_index`/`.debug_tu_index`) are not supported
+    /// yet; `id` must resolve to a single compilation unit's own `.dwo` file.
+    fn read_dwo(id_ptr: *const u8, id_len: usize, dest: *mut u8, offset: usize, len: usize) -> usize;
+
+    /// Called during address_to_frame/addresses_to_frames so that JS can copy the frame data out of
+    /// the wasm memory. If `symbol` is null, the original line should be used.
+    /// `is_inline` is 0 for the real return-address frame and 1 for a frame synthesized from an
+    /// inlined subroutine; when an address inlines several calls, `on_frame` is invoked once per
+    /// frame, innermost first, with the physical frame (`is_inline == 0`) reported last.
+    /// `index` is the position of the resolved address within the request (always 0 for
+    /// `address_to_frame`; the array index into `addresses_to_frames`'s input for the batched call),
+    /// so JS can tell which input address a given frame belongs to.
     /// Ideally address_to_frame would just return the frame data but since Rust doesn't support multiple return values we
     /// use this callback instead.
     fn on_frame(
@@ -22,6 +37,20 @@ unsafe extern "C" {
         location_len: usize,
         line: u32,
         column: u32,
+        is_inline: u32,
+        index: u32,
+    );
+
+    /// Called once per entry found by `range_to_lines`, covering a contiguous span of `len` wasm
+    /// addresses starting at `addr` that all map to the same source location. `file` is null if the
+    /// location has no associated file.
+    fn on_range_entry(
+        addr: usize,
+        len: usize,
+        file: *const u8,
+        file_len: usize,
+        line: u32,
+        column: u32,
     );
 
     /// This can be called during init or address_to_frame to report an error. If this gets called, call did not succeed.
@@ -62,9 +91,86 @@ impl Seek for ModuleReader {
 }
 
 type Context = addr2line::Context<gimli::EndianSlice<'static, gimli::LittleEndian>>;
+type SplitDwarf = gimli::Dwarf<gimli::EndianSlice<'static, gimli::LittleEndian>>;
 
 static mut CONTEXT: LazyLock<RwLock<Option<Arc<Context>>>> = LazyLock::new(|| RwLock::new(None));
 
+/// The `<code>` section offset returned by the most recent `init`, kept around so `range_to_lines`
+/// can apply the same wasm-address-to-DWARF-address correction that callers apply themselves before
+/// calling `address_to_frame`/`addresses_to_frames`.
+static mut CODE_SECTION_OFFSET: LazyLock<RwLock<usize>> = LazyLock::new(|| RwLock::new(0));
+
+fn get_code_section_offset() -> usize {
+    unsafe { *CODE_SECTION_OFFSET.read().unwrap() }
+}
+
+fn set_code_section_offset(offset: usize) {
+    unsafe {
+        *CODE_SECTION_OFFSET.write().unwrap() = offset;
+    }
+}
+
+/// Standalone split DWARF units (`.dwo` files) already loaded from the host, keyed by `dwo_id` so
+/// that repeated addresses into the same compilation unit don't re-fetch and re-parse it.
+static mut DWO_CACHE: LazyLock<RwLock<HashMap<gimli::DwoId, Arc<SplitDwarf>>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Buffers produced by `decompress_section`, for sections compressed via ELF `SHF_COMPRESSED`/zstd
+/// or the legacy `.zdebug_*` wire format, leaked to back the `'static` `EndianSlice`s the DWARF
+/// context holds onto. Tracked here (rather than leaked and forgotten) so a later `init` call for a
+/// new module can free the previous module's buffers.
+static mut LEAKED_SECTION_BUFFERS: LazyLock<RwLock<Vec<Box<[u8]>>>> =
+    LazyLock::new(|| RwLock::new(Vec::new()));
+
+/// Leaks `data` to obtain the `'static` slice `EndianSlice<'static, _>` requires, while keeping it
+/// reachable through `LEAKED_SECTION_BUFFERS` so it can be dropped later instead of leaking for the
+/// lifetime of the program.
+fn leak_and_track(data: Vec<u8>) -> &'static [u8] {
+    let boxed = data.into_boxed_slice();
+    unsafe {
+        let mut buffers = LEAKED_SECTION_BUFFERS.write().unwrap();
+        buffers.push(boxed);
+        // SAFETY: the slice stays alive as long as `buffers` holds its `Box`, and nothing drops
+        // that entry before the next `init_impl` call clears the whole module's state.
+        std::mem::transmute::<&[u8], &'static [u8]>(&buffers.last().unwrap()[..])
+    }
+}
+
+/// Drops the decompressed section buffers leaked for the previously initialized module.
+fn clear_leaked_section_buffers() {
+    unsafe {
+        LEAKED_SECTION_BUFFERS.write().unwrap().clear();
+    }
+}
+
+/// Decompresses a DWARF section, however it reached us compressed: an owned buffer handed back by
+/// `object::Section::uncompressed_data` on the `.dwo` path (`load_split_dwarf`), or the raw bytes
+/// `read_section_range` pulls straight out of the wasm module's custom sections on the main-module
+/// path (`init_impl`). The latter is how the legacy `.zdebug_*` wire format shows up: a zlib stream
+/// prefixed with the literal ASCII magic `"ZLIB"` followed by an 8-byte big-endian
+/// uncompressed size, which we strip before inflating. Once any such header is stripped, pick zlib
+/// (the common case, via `miniz_oxide`) or zstd by inspecting the compression header.
+fn decompress_section(raw: &[u8]) -> Result<Vec<u8>, String> {
+    const ZDEBUG_MAGIC: &[u8] = b"ZLIB";
+    const ZDEBUG_HEADER_LEN: usize = ZDEBUG_MAGIC.len() + 8;
+    let raw = if raw.starts_with(ZDEBUG_MAGIC) && raw.len() >= ZDEBUG_HEADER_LEN {
+        &raw[ZDEBUG_HEADER_LEN..]
+    } else {
+        raw
+    };
+
+    // A zlib stream's first byte encodes the compression method (CM) in its low nibble; CM == 8 is
+    // "deflate", which is what rustc's zlib-compressed debug sections use.
+    if raw.len() >= 2 && raw[0] & 0x0f == 8 {
+        miniz_oxide::inflate::decompress_to_vec_zlib(raw)
+            .map_err(|e| format!("zlib decompression failed: {e:?}"))
+    } else if raw.starts_with(&zstd::zstd_safe::MAGICNUMBER.to_le_bytes()) {
+        zstd::stream::decode_all(raw).map_err(|e| e.to_string())
+    } else {
+        Err("Unrecognized compressed section format".to_string())
+    }
+}
+
 fn get_context() -> Option<Arc<Context>> {
     unsafe {
         let lock = CONTEXT.read().unwrap();
@@ -97,56 +203,206 @@ extern "C" fn init(len: usize) -> i32 {
 }
 
 fn init_impl(len: usize) -> Result<usize, Error> {
-    // Create a `ModuleReader` that can read chunks of the wasm module as needed.
-    // This reader must outlive this function, however, afaict the `object` crate only works with
-    // borrowed data, so we must leak it to give it a 'static lifetime.
-    // TODO: use `wasmparser` instead of `object` since the object create loads the entire module into memory.
-    let reader = ModuleReader::new(len);
-    let cached_reader = Box::leak(Box::new(object::ReadCache::new(reader)));
-
-    // The object crate parses the wasm module and determines the location of each section.
-    let object = object::wasm::WasmFile::parse(cached_reader as &_).map_err(|e| e.to_string())?;
-
-    // Determine the offset of the <code> section within the wasm module. Addresses reported in the
-    // browser's callstack are relative to the wasm module, but DWARF addresses are relative to the
-    // code section. We use `code_section_offset` to convert callstack addresses into DWARF
-    // addresses.
-    let code_section_offset = object
-        .section_by_name("<code>")
-        .and_then(|section| section.file_range())
-        .map(|range| range.0 as usize)
-        .ok_or("Code section not found")?;
-
-    // Create the `Dwarf` structure.
+    // Starting a new module invalidates any decompressed sections leaked for the previous one, as
+    // well as anything resolved against the previous module's addresses.
+    clear_leaked_section_buffers();
+    unsafe {
+        *CONTEXT.write().unwrap() = None;
+        DWO_CACHE.write().unwrap().clear();
+        SYMBOL_CACHE.write().unwrap().clear();
+    }
+
+    // Stream the module's section headers with `wasmparser` rather than materializing the whole
+    // module through `object`: this only has to buffer one section at a time, so peak memory is
+    // bounded by the largest single section plus the DWARF actually consulted, rather than the full
+    // module size.
+    let sections = discover_sections(len)?;
+
+    // Create the `Dwarf` structure, reading each section's bytes from the host lazily, the first
+    // time `gimli` actually asks for it, instead of eagerly pulling in every `.debug_*` section.
     let dwarf = gimli::Dwarf::load(|id| {
-        let section = object.section_by_name(id.name());
-        let Some(section) = section else {
+        let Some(range) = sections.debug_sections.get(id.name()) else {
             // Return an empty section if the section does not exist.
             return Ok(gimli::EndianSlice::new(&[], gimli::LittleEndian));
         };
-        let data = match section.uncompressed_data().map_err(|e| e.to_string())? {
-            Cow::Borrowed(b) => Ok(b),
-            Cow::Owned(_b) => Err("Compressed section not supported yet"),
-        }?;
-        Ok::<_, String>(gimli::EndianSlice::new(&data, gimli::LittleEndian))
+        let raw = read_section_range(len, range).map_err(|e| e.0.to_string())?;
+        let data: &'static [u8] = match decompress_section(&raw) {
+            Ok(decompressed) => leak_and_track(decompressed),
+            Err(_) => leak_and_track(raw),
+        };
+        Ok::<_, String>(gimli::EndianSlice::new(data, gimli::LittleEndian))
     })
     .map_err(|e| e.to_string())?;
 
     // Create the `addr2line::Context` that knows how to map each address into its symbol and location.
     let ctx = Arc::new(Context::from_dwarf(dwarf).unwrap());
     set_context(ctx);
+    set_code_section_offset(sections.code_section_offset);
+
+    Ok(sections.code_section_offset)
+}
+
+/// Byte range of a custom section within the wasm module, as discovered by `discover_sections`.
+struct SectionRange {
+    offset: usize,
+    len: usize,
+}
+
+/// The `<code>` section's offset (used to convert callstack addresses into DWARF addresses) plus
+/// the byte range of each `.debug_*` custom section. Only the ranges are kept: the contents read
+/// while discovering them are dropped, and `read_section_range` reads a section again when needed.
+struct DiscoveredSections {
+    code_section_offset: usize,
+    debug_sections: HashMap<String, SectionRange>,
+}
+
+/// Walks the module's sections with `wasmparser`'s streaming `Parser`, pulling in bytes as the
+/// parser asks for them and dropping each section once it is parsed, so the whole module never has
+/// to be resident in memory at once. The parser only yields a section once its full contents are
+/// buffered, so every section, `.debug_*` ones included, is read from the host during this walk.
+fn discover_sections(len: usize) -> Result<DiscoveredSections, Error> {
+    let mut reader = ModuleReader::new(len);
+    let mut parser = wasmparser::Parser::new(0);
+    let mut buf = Vec::new();
+    let mut eof = false;
+    let mut code_section_offset = None;
+    let mut debug_sections = HashMap::new();
 
-    Ok(code_section_offset)
+    loop {
+        match parser.parse(&buf, eof).map_err(|e| e.to_string())? {
+            wasmparser::Chunk::NeedMoreData(hint) => {
+                if eof {
+                    // The module is exhausted but the parser still wants more: nothing further to
+                    // discover, so stop with whatever sections we've already found.
+                    break;
+                }
+                let start = buf.len();
+                buf.resize(start + hint as usize, 0);
+                let read = reader.read(&mut buf[start..]).map_err(|e| e.to_string())?;
+                buf.truncate(start + read);
+                eof = read == 0;
+            }
+            wasmparser::Chunk::Parsed { consumed, payload } => {
+                match payload {
+                    wasmparser::Payload::CodeSectionStart { range, .. } => {
+                        code_section_offset = Some(range.start);
+                    }
+                    wasmparser::Payload::CustomSection(reader)
+                        if reader.name().starts_with(".debug") || reader.name().starts_with(".zdebug") =>
+                    {
+                        // Canonicalize the legacy `.zdebug_*` prefix to the `.debug_*` name
+                        // `gimli::SectionId::name()` looks sections up by; `decompress_section`
+                        // (chunk0-3) is what actually inflates the still-compressed bytes.
+                        let name = reader.name().replacen(".zdebug", ".debug", 1);
+                        debug_sections.insert(
+                            name,
+                            SectionRange {
+                                offset: reader.data_offset(),
+                                len: reader.data().len(),
+                            },
+                        );
+                    }
+                    wasmparser::Payload::End(_) => break,
+                    _ => {}
+                }
+                buf.drain(..consumed);
+            }
+        }
+    }
+
+    Ok(DiscoveredSections {
+        code_section_offset: code_section_offset.ok_or("Code section not found")?,
+        debug_sections,
+    })
+}
+
+/// Reads exactly `range`'s bytes out of the wasm module, seeking a fresh `ModuleReader` to its
+/// offset first. Called lazily from the `gimli::Dwarf::load` closure, once per section actually
+/// consulted.
+fn read_section_range(module_len: usize, range: &SectionRange) -> Result<Vec<u8>, Error> {
+    let mut reader = ModuleReader::new(module_len);
+    reader
+        .seek(SeekFrom::Start(range.offset as u64))
+        .map_err(|e| e.to_string())?;
+    let mut data = vec![0u8; range.len];
+    let mut read_total = 0;
+    while read_total < data.len() {
+        let read = reader.read(&mut data[read_total..]).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        read_total += read;
+    }
+    data.truncate(read_total);
+    Ok(data)
 }
 
 #[unsafe(no_mangle)]
 extern "C" fn address_to_frame(address: usize) {
-    match address_to_line_impl(address) {
+    match resolve_frames(address as u64) {
+        Ok(frames) => emit_frames(&frames, 0),
+        Err(e) => e.report(),
+    }
+}
+
+/// Batched form of `address_to_frame`: resolves every address in the `addresses` array (laid out in
+/// this module's own memory at `ptr`, `len` elements) and invokes `on_frame` for each resulting
+/// frame, tagging it with the index of the address it came from. Lets a caller symbolize a whole
+/// callstack with a single boundary crossing instead of one per frame.
+#[unsafe(no_mangle)]
+extern "C" fn addresses_to_frames(ptr: *const u32, len: usize) {
+    let addresses = unsafe { std::slice::from_raw_parts(ptr, len) };
+    for (index, &address) in addresses.iter().enumerate() {
+        match resolve_frames(address as u64) {
+            Ok(frames) => emit_frames(&frames, index as u32),
+            Err(e) => e.report(),
+        }
+    }
+}
+
+/// Symbolizes the whole `[start, end)` span of wasm addresses in one pass, reporting each
+/// contiguous sub-range that maps to the same source location through `on_range_entry`. Useful for
+/// mapping an entire function body or a profiler's sampled region without probing address-by-address.
+#[unsafe(no_mangle)]
+extern "C" fn range_to_lines(start: usize, end: usize) {
+    match range_to_lines_impl(start, end) {
         Ok(_) => {}
-        Err(e) => {
-            e.report();
+        Err(e) => e.report(),
+    }
+}
+
+fn range_to_lines_impl(start: usize, end: usize) -> Result<(), Error> {
+    let ctx = get_context().ok_or("Context not found")?;
+    let offset = get_code_section_offset();
+    let dwarf_start = start
+        .checked_sub(offset)
+        .ok_or("Range start before code section")? as u64;
+    let dwarf_end = end
+        .checked_sub(offset)
+        .ok_or("Range end before code section")? as u64;
+
+    let entries = ctx
+        .find_location_range(dwarf_start, dwarf_end)
+        .map_err(|e| e.to_string())?;
+    for (range_start, range_len, location) in entries {
+        let addr = range_start as usize + offset;
+        let len = range_len as usize;
+        let (file, file_len) = location
+            .file
+            .map(|f| (f.as_ptr(), f.len()))
+            .unwrap_or((std::ptr::null(), 0));
+        unsafe {
+            on_range_entry(
+                addr,
+                len,
+                file,
+                file_len,
+                location.line.unwrap_or(0),
+                location.column.unwrap_or(0),
+            );
         }
     }
+    Ok(())
 }
 
 struct Error(Cow<'static, str>);
@@ -167,42 +423,207 @@ impl Error {
     }
 }
 
-fn address_to_line_impl(address: usize) -> Result<(), Error> {
+/// Reads the full contents of the split DWARF object named `id` from the host. Split DWARF files
+/// are small compared to the main module, so unlike `ModuleReader` we just pull the whole thing
+/// into a `Vec` up front instead of streaming it through `object::ReadCache`.
+fn read_dwo_bytes(id: &str) -> Vec<u8> {
+    const CHUNK: usize = 4096;
+    let mut data = Vec::new();
+    loop {
+        let mut chunk = vec![0u8; CHUNK];
+        let read = unsafe { read_dwo(id.as_ptr(), id.len(), chunk.as_mut_ptr(), data.len(), CHUNK) };
+        if read == 0 {
+            break;
+        }
+        chunk.truncate(read);
+        data.extend_from_slice(&chunk);
+    }
+    data
+}
+
+/// Resolves a `gimli::SplitDwarfLoad` by fetching a standalone `.dwo`'s bytes from the host, parsing
+/// them with `object`, and building the `gimli::Dwarf` addr2line needs to keep walking the inline
+/// chain. Loaded units are cached by `dwo_id` since the same compilation unit is usually revisited
+/// across a callstack.
+///
+/// This only handles standalone `.dwo` files, each holding one compilation unit's own sections.
+/// Bundled `.dwp` packages multiplex many units' sections through a `.debug_cu_index`/
+/// `.debug_tu_index`, which needs `gimli::DwarfPackage::find_cu` rather than a plain `object` parse,
+/// and isn't implemented here.
+fn load_split_dwarf(
+    load: &gimli::SplitDwarfLoad<gimli::EndianSlice<'static, gimli::LittleEndian>>,
+) -> Option<Arc<SplitDwarf>> {
+    if let Some(cached) = unsafe { DWO_CACHE.read().unwrap().get(&load.dwo_id) } {
+        return Some(cached.clone());
+    }
+
+    let comp_dir = load.comp_dir.map(|s| String::from_utf8_lossy(s.slice()).into_owned());
+    let path = load.path.map(|s| String::from_utf8_lossy(s.slice()).into_owned())?;
+    let id = match comp_dir {
+        Some(comp_dir) => format!("{comp_dir}/{path}"),
+        None => path,
+    };
+
+    let bytes = read_dwo_bytes(&id);
+    if bytes.is_empty() {
+        return None;
+    }
+    // The object must outlive this function to back the `EndianSlice<'static, _>` below, so its
+    // bytes are leaked. Unlike the buffers in `LEAKED_SECTION_BUFFERS`, they are never freed.
+    let bytes: &'static [u8] = Box::leak(bytes.into_boxed_slice());
+    let object = object::File::parse(bytes).ok()?;
+
+    let dwarf = gimli::Dwarf::load(|id| {
+        let section = object.section_by_name(id.name());
+        let Some(section) = section else {
+            return Ok::<_, String>(gimli::EndianSlice::new(&[], gimli::LittleEndian));
+        };
+        let data = match section.uncompressed_data().map_err(|e| e.to_string())? {
+            Cow::Borrowed(b) => b,
+            Cow::Owned(raw) => leak_and_track(decompress_section(&raw)?),
+        };
+        Ok(gimli::EndianSlice::new(data, gimli::LittleEndian))
+    })
+    .ok()?;
+
+    let dwarf = Arc::new(dwarf);
+    unsafe {
+        DWO_CACHE
+            .write()
+            .unwrap()
+            .insert(load.dwo_id, dwarf.clone());
+    }
+    Some(dwarf)
+}
+
+/// A fully-resolved frame, owned so it can outlive the `addr2line::Frame` it was built from and be
+/// kept around in `SYMBOL_CACHE`.
+struct CachedFrame {
+    symbol: Option<Box<str>>,
+    location: Option<Box<str>>,
+    line: u32,
+    column: u32,
+    is_inline: u32,
+}
+
+/// Mirrors `backtrace`'s `MAPPINGS_CACHE_SIZE`: a small fixed-size cache is enough to amortize the
+/// repeated addresses a recursive callstack (e.g. `fib`/`detour`) produces, without holding onto
+/// every address a long-running program ever resolves.
+const SYMBOL_CACHE_SIZE: usize = 4;
+
+static mut SYMBOL_CACHE: LazyLock<RwLock<VecDeque<(u64, Arc<[CachedFrame]>)>>> =
+    LazyLock::new(|| RwLock::new(VecDeque::new()));
+
+/// Looks up `address` in `SYMBOL_CACHE`, promoting it to most-recently-used on a hit.
+fn cached_frames_for(address: u64) -> Option<Arc<[CachedFrame]>> {
+    unsafe {
+        let mut cache = SYMBOL_CACHE.write().unwrap();
+        let pos = cache.iter().position(|(cached, _)| *cached == address)?;
+        let entry = cache.remove(pos).unwrap();
+        let frames = entry.1.clone();
+        cache.push_front(entry);
+        Some(frames)
+    }
+}
+
+/// Inserts `frames` as the most-recently-used entry, evicting the least-recently-used one once the
+/// cache is full.
+fn cache_frames(address: u64, frames: Arc<[CachedFrame]>) {
+    unsafe {
+        let mut cache = SYMBOL_CACHE.write().unwrap();
+        if cache.len() >= SYMBOL_CACHE_SIZE {
+            cache.pop_back();
+        }
+        cache.push_front((address, frames));
+    }
+}
+
+/// Resolves `address` into its full inline chain, consulting `SYMBOL_CACHE` first so repeated
+/// addresses don't re-walk the DWARF line program.
+fn resolve_frames(address: u64) -> Result<Arc<[CachedFrame]>, Error> {
+    if let Some(frames) = cached_frames_for(address) {
+        return Ok(frames);
+    }
+
     let ctx = get_context().ok_or("Context not found")?;
-    let mut frames = match ctx.find_frames(address as u64) {
-        addr2line::LookupResult::Output(output) => output.map_err(|e| e.to_string())?,
-        addr2line::LookupResult::Load { .. } => {
-            return Err("Split DWARF not supported yet".into());
+    let mut result = ctx.find_frames(address);
+    let mut frames = loop {
+        match result {
+            addr2line::LookupResult::Output(output) => break output.map_err(|e| e.to_string())?,
+            addr2line::LookupResult::Load { load, continuation } => {
+                let dwo_dwarf = load_split_dwarf(&load);
+                result = continuation.resume(dwo_dwarf);
+            }
         }
     };
-    // TODO: addr2line can return multiple frames per address because of inlined functions. For now
-    // we return the first frame.
+    // `find_frames` yields the full inline chain for this address, innermost-first, ending with
+    // the physical frame whose location comes from the line-number program. Collect them all so
+    // we know which one is last before reporting `is_inline` to JS.
+    let mut collected = Vec::new();
     while let Some(frame) = frames.next().map_err(|e| e.to_string())? {
-        let symbol = frame
-            .function
-            .as_ref()
-            .map(|f| f.demangle().unwrap_or_else(|_| f.name.to_string_lossy()));
-        let location = frame.location.as_ref().and_then(|l| l.file);
-        unsafe {
-            let (symbol, symbol_len) = symbol
+        collected.push(frame);
+    }
+    if collected.is_empty() {
+        return Err("No frame found".into());
+    }
+    let last_index = collected.len() - 1;
+    let resolved: Vec<CachedFrame> = collected
+        .into_iter()
+        .enumerate()
+        .map(|(index, frame)| {
+            let symbol = frame.function.as_ref().map(|f| {
+                f.demangle()
+                    .unwrap_or_else(|_| f.name.to_string_lossy())
+                    .into_owned()
+                    .into_boxed_str()
+            });
+            let location = frame
+                .location
                 .as_ref()
-                .map(|s| (s.as_bytes(), s.len()))
-                .unwrap_or((&[], 0));
-            let (location, location_len) = location
-                .map(|l| (l.as_bytes(), l.len()))
-                .unwrap_or((&[], 0));
-            let line = frame.location.as_ref().and_then(|l| l.line);
-            let column = frame.location.as_ref().and_then(|l| l.column);
+                .and_then(|l| l.file)
+                .map(|f| f.to_string().into_boxed_str());
+            let line = frame.location.as_ref().and_then(|l| l.line).unwrap_or(0);
+            let column = frame.location.as_ref().and_then(|l| l.column).unwrap_or(0);
+            CachedFrame {
+                symbol,
+                location,
+                line,
+                column,
+                is_inline: if index == last_index { 0 } else { 1 },
+            }
+        })
+        .collect();
+
+    let resolved: Arc<[CachedFrame]> = resolved.into();
+    cache_frames(address, resolved.clone());
+    Ok(resolved)
+}
+
+/// Reports every frame in `frames` through `on_frame`, tagging each with `index` so the caller can
+/// tell which requested address they resolve to.
+fn emit_frames(frames: &[CachedFrame], index: u32) {
+    for frame in frames {
+        let (symbol, symbol_len) = frame
+            .symbol
+            .as_deref()
+            .map(|s| (s.as_ptr(), s.len()))
+            .unwrap_or((std::ptr::null(), 0));
+        let (location, location_len) = frame
+            .location
+            .as_deref()
+            .map(|s| (s.as_ptr(), s.len()))
+            .unwrap_or((std::ptr::null(), 0));
+        unsafe {
             on_frame(
-                symbol.as_ptr(),
+                symbol,
                 symbol_len,
-                location.as_ptr(),
+                location,
                 location_len,
-                line.unwrap_or(0),
-                column.unwrap_or(0),
+                frame.line,
+                frame.column,
+                frame.is_inline,
+                index,
             );
-            return Ok(());
         }
     }
-    Err("No frame found".into())
 }